@@ -0,0 +1,379 @@
+//! Code block extraction and save-to-file command
+//!
+//! Extracts fenced code blocks from an assistant message and writes the selected
+//! blocks into the workspace once the user has approved the write.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+/// A fenced code block found in a markdown message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CodeBlock {
+    pub index: usize,
+    pub language: Option<String>,
+    pub code: String,
+}
+
+/// Selects a code block and the workspace-relative path it should be written to
+#[derive(Debug, Clone, Deserialize)]
+pub struct CodeBlockTarget {
+    /// Zero-based block index within the message
+    pub index: Option<usize>,
+    /// Language tag; picks the first matching block when no index is given
+    pub language: Option<String>,
+    /// Destination path relative to the workspace root
+    pub path: String,
+}
+
+fn parse_fence(line: &str) -> Option<(char, usize, &str)> {
+    let trimmed = line.trim_start();
+    let fence_char = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let fence_len = trimmed.chars().take_while(|c| *c == fence_char).count();
+    if fence_len < 3 {
+        return None;
+    }
+    Some((fence_char, fence_len, trimmed[fence_len..].trim()))
+}
+
+/// Extract all fenced code blocks (``` or ~~~) from markdown content.
+///
+/// An unterminated trailing block is still returned so partially streamed
+/// messages can be saved.
+pub fn extract_code_blocks(content: &str) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    let mut open: Option<(char, usize, Option<String>, Vec<&str>)> = None;
+
+    for line in content.lines() {
+        match open.as_mut() {
+            None => {
+                if let Some((fence_char, fence_len, info)) = parse_fence(line) {
+                    let language = info.split_whitespace().next().map(|lang| lang.to_string());
+                    open = Some((fence_char, fence_len, language, Vec::new()));
+                }
+            }
+            Some((fence_char, fence_len, _, lines)) => {
+                let closes = parse_fence(line).is_some_and(|(c, len, rest)| {
+                    c == *fence_char && len >= *fence_len && rest.is_empty()
+                });
+                if !closes {
+                    lines.push(line);
+                    continue;
+                }
+
+                if let Some((_, _, language, lines)) = open.take() {
+                    blocks.push(CodeBlock {
+                        index: blocks.len(),
+                        language,
+                        code: lines.join("\n"),
+                    });
+                }
+            }
+        }
+    }
+
+    if let Some((_, _, language, lines)) = open {
+        blocks.push(CodeBlock {
+            index: blocks.len(),
+            language,
+            code: lines.join("\n"),
+        });
+    }
+
+    blocks
+}
+
+fn language_matches(block: &CodeBlock, language: &str) -> bool {
+    block
+        .language
+        .as_deref()
+        .is_some_and(|lang| lang.eq_ignore_ascii_case(language.trim()))
+}
+
+fn select_block<'a>(
+    blocks: &'a [CodeBlock],
+    target: &CodeBlockTarget,
+) -> Result<&'a CodeBlock, String> {
+    match (target.index, target.language.as_deref()) {
+        (Some(index), language) => {
+            let block = blocks
+                .get(index)
+                .ok_or_else(|| format!("Code block #{} not found in message", index))?;
+            if let Some(language) = language {
+                if !language_matches(block, language) {
+                    return Err(format!(
+                        "Code block #{} is not tagged as '{}'",
+                        index, language
+                    ));
+                }
+            }
+            Ok(block)
+        }
+        (None, Some(language)) => blocks
+            .iter()
+            .find(|block| language_matches(block, language))
+            .ok_or_else(|| format!("No '{}' code block found in message", language)),
+        (None, None) => Err(format!(
+            "Target '{}' must specify a block index or language",
+            target.path
+        )),
+    }
+}
+
+/// Resolve a workspace-relative path, rejecting anything that could escape the workspace.
+///
+/// `workspace` must be canonical. Every existing prefix of the target is
+/// canonicalized so a symlink inside the workspace cannot redirect the write.
+fn resolve_workspace_path(workspace: &Path, relative: &str) -> Result<PathBuf, String> {
+    let relative = relative.trim();
+    if relative.is_empty() {
+        return Err("Target path must not be empty".to_string());
+    }
+
+    let outside_workspace = || format!("Target path '{}' must stay inside the workspace", relative);
+    let mut parts = Vec::new();
+    for component in Path::new(relative).components() {
+        match component {
+            Component::Normal(part) => parts.push(part),
+            Component::CurDir => {}
+            _ => return Err(outside_workspace()),
+        }
+    }
+    if parts.is_empty() {
+        return Err(format!("Target path '{}' must name a file", relative));
+    }
+
+    let mut resolved = workspace.to_path_buf();
+    for (position, part) in parts.iter().enumerate() {
+        let candidate = resolved.join(part);
+        if candidate.symlink_metadata().is_err() {
+            // Everything from here on is created by the write itself.
+            if !resolved.is_dir() {
+                return Err(format!("{:?} is not a directory", resolved));
+            }
+            return Ok(parts[position..]
+                .iter()
+                .fold(resolved, |path, part| path.join(part)));
+        }
+
+        resolved = candidate
+            .canonicalize()
+            .map_err(|e| format!("Failed to resolve {:?}: {}", candidate, e))?;
+        if !resolved.starts_with(workspace) {
+            return Err(outside_workspace());
+        }
+    }
+
+    if resolved.is_dir() {
+        return Err(format!("Target path '{}' is a directory", relative));
+    }
+    Ok(resolved)
+}
+
+/// Pair each target with its block and resolved destination.
+fn plan_writes<'a>(
+    workspace: &Path,
+    blocks: &'a [CodeBlock],
+    targets: &[CodeBlockTarget],
+) -> Result<Vec<(PathBuf, &'a CodeBlock)>, String> {
+    let mut seen = HashSet::new();
+    let mut writes = Vec::with_capacity(targets.len());
+    for target in targets {
+        let block = select_block(blocks, target)?;
+        let path = resolve_workspace_path(workspace, &target.path)?;
+        if !seen.insert(path.clone()) {
+            return Err(format!("Multiple targets resolve to {:?}", path));
+        }
+        writes.push((path, block));
+    }
+    Ok(writes)
+}
+
+async fn confirm_write(app: &tauri::AppHandle, paths: &[PathBuf]) -> bool {
+    let listing = paths
+        .iter()
+        .map(|path| {
+            let marker = if path.exists() { " (overwrite)" } else { "" };
+            format!("• {}{}", path.display(), marker)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app.dialog()
+        .message(format!("Write the selected code blocks to:\n\n{}", listing))
+        .title("Save code blocks")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Write Files".to_string(),
+            "Cancel".to_string(),
+        ))
+        .show(move |accepted| {
+            let _ = tx.send(accepted);
+        });
+
+    rx.await.unwrap_or(false)
+}
+
+/// List the fenced code blocks in `content`.
+///
+/// Indices match the ones `save_code_blocks` selects by.
+#[tauri::command]
+pub fn list_code_blocks(content: String) -> Vec<CodeBlock> {
+    extract_code_blocks(&content)
+}
+
+/// Write selected code blocks from `content` into `workspace_path`.
+///
+/// Returns the absolute paths that were written. If a write fails, the error
+/// lists the files that were already written.
+#[tauri::command]
+pub async fn save_code_blocks(
+    app: tauri::AppHandle,
+    content: String,
+    workspace_path: String,
+    targets: Vec<CodeBlockTarget>,
+) -> Result<Vec<String>, String> {
+    if targets.is_empty() {
+        return Err("No code block targets provided".to_string());
+    }
+
+    let workspace = PathBuf::from(workspace_path.trim())
+        .canonicalize()
+        .map_err(|e| format!("Invalid workspace path: {}", e))?;
+    if !workspace.is_dir() {
+        return Err(format!("Workspace {:?} is not a directory", workspace));
+    }
+
+    let blocks = extract_code_blocks(&content);
+    let writes = plan_writes(&workspace, &blocks, &targets)?;
+
+    let paths: Vec<PathBuf> = writes.iter().map(|(path, _)| path.clone()).collect();
+    if !confirm_write(&app, &paths).await {
+        return Err("Save cancelled by user".to_string());
+    }
+
+    // The dialog can stay open for a while; make sure nothing (e.g. a new
+    // symlink) changed where the approved paths resolve to.
+    let writes = plan_writes(&workspace, &blocks, &targets)?;
+    if writes.iter().map(|(path, _)| path).ne(paths.iter()) {
+        return Err(
+            "Target paths changed while waiting for confirmation; nothing was written".to_string(),
+        );
+    }
+
+    let mut written: Vec<String> = Vec::with_capacity(writes.len());
+    for (path, block) in writes {
+        let mut code = block.code.clone();
+        if !code.ends_with('\n') {
+            code.push('\n');
+        }
+        let result = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| std::fs::write(&path, code));
+        if let Err(error) = result {
+            let mut message = format!("Failed to write {:?}: {}", path, error);
+            if !written.is_empty() {
+                message.push_str(&format!("; already written: {}", written.join(", ")));
+            }
+            return Err(message);
+        }
+        log::info!("Saved code block #{} to {:?}", block.index, path);
+        written.push(path.to_string_lossy().to_string());
+    }
+
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSAGE: &str = "Here you go:\n\n```rust\nfn main() {}\n```\n\nand\n\n~~~~python title\nprint('hi')\n```\nstill python\n~~~~\n\n```\nplain\n";
+
+    #[test]
+    fn extracts_blocks_with_languages() {
+        let blocks = extract_code_blocks(MESSAGE);
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0].language.as_deref(), Some("rust"));
+        assert_eq!(blocks[0].code, "fn main() {}");
+        assert_eq!(blocks[1].language.as_deref(), Some("python"));
+        assert_eq!(blocks[1].code, "print('hi')\n```\nstill python");
+        assert_eq!(blocks[2].language, None);
+        assert_eq!(blocks[2].code, "plain");
+    }
+
+    #[test]
+    fn selects_block_by_language_or_index() {
+        let blocks = extract_code_blocks(MESSAGE);
+        let by_language = CodeBlockTarget {
+            index: None,
+            language: Some("Python".to_string()),
+            path: "a.py".to_string(),
+        };
+        assert_eq!(select_block(&blocks, &by_language).unwrap().index, 1);
+
+        let mismatched = CodeBlockTarget {
+            index: Some(0),
+            language: Some("python".to_string()),
+            path: "a.py".to_string(),
+        };
+        assert!(select_block(&blocks, &mismatched).is_err());
+    }
+
+    fn temp_dir() -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path().canonicalize().unwrap();
+        (dir, workspace)
+    }
+
+    #[test]
+    fn rejects_paths_escaping_workspace() {
+        let (_dir, workspace) = temp_dir();
+        assert_eq!(
+            resolve_workspace_path(&workspace, "src/main.rs").unwrap(),
+            workspace.join("src/main.rs")
+        );
+        assert!(resolve_workspace_path(&workspace, "../etc/passwd").is_err());
+        assert!(resolve_workspace_path(&workspace, "/etc/passwd").is_err());
+        assert!(resolve_workspace_path(&workspace, "  ").is_err());
+        assert!(resolve_workspace_path(&workspace, ".").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_symlinks_escaping_workspace() {
+        let (_dir, workspace) = temp_dir();
+        let (_other_dir, other) = temp_dir();
+        std::os::unix::fs::symlink(&other, workspace.join("link")).unwrap();
+        std::os::unix::fs::symlink(other.join("missing"), workspace.join("dangling")).unwrap();
+        std::fs::create_dir(workspace.join("src")).unwrap();
+        std::os::unix::fs::symlink(workspace.join("src"), workspace.join("alias")).unwrap();
+
+        assert!(resolve_workspace_path(&workspace, "link/pwned.txt").is_err());
+        assert!(resolve_workspace_path(&workspace, "link").is_err());
+        assert!(resolve_workspace_path(&workspace, "dangling").is_err());
+        assert_eq!(
+            resolve_workspace_path(&workspace, "alias/lib.rs").unwrap(),
+            workspace.join("src/lib.rs")
+        );
+    }
+
+    #[test]
+    fn rejects_targets_resolving_to_the_same_file() {
+        let (_dir, workspace) = temp_dir();
+        let blocks = extract_code_blocks(MESSAGE);
+        let target = |index, path: &str| CodeBlockTarget {
+            index: Some(index),
+            language: None,
+            path: path.to_string(),
+        };
+
+        let distinct = [target(0, "a.rs"), target(2, "b.txt")];
+        assert!(plan_writes(&workspace, &blocks, &distinct).is_ok());
+        let duplicate = [target(0, "a.rs"), target(1, "./a.rs")];
+        assert!(plan_writes(&workspace, &blocks, &duplicate).is_err());
+    }
+}
@@ -0,0 +1,77 @@
+use crate::app_settings;
+use crate::connection::{
+    normalize_remote_url, ApiKeyPersistence, ConnectionMode, ConnectionSettings, ConnectionStatus,
+};
+use crate::ConnectionState;
+
+#[tauri::command]
+pub async fn get_connection_status(
+    state: tauri::State<'_, ConnectionState>,
+) -> Result<ConnectionStatus, String> {
+    Ok(state.0.status().await)
+}
+
+#[tauri::command]
+pub async fn get_connection_config(
+    state: tauri::State<'_, ConnectionState>,
+) -> Result<serde_json::Value, String> {
+    let settings = state.0.settings().await;
+    let status = state.0.status().await;
+
+    Ok(serde_json::json!({
+        "mode": settings.mode,
+        "remote_url": settings.remote_url,
+        "has_api_key": settings.api_key.is_some(),
+        "base_url": status.base_url,
+    }))
+}
+
+/// Switch between the embedded service and a remote Bamboo web service.
+///
+/// `api_key: None` keeps the current key; an empty string clears it.
+/// `remember` stores the resulting key encrypted in config.json. Without it, a
+/// newly entered key is only kept for this session, while a kept key is left
+/// on disk exactly as it was stored.
+#[tauri::command]
+pub async fn set_connection_config(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, ConnectionState>,
+    mode: String,
+    remote_url: Option<String>,
+    api_key: Option<String>,
+    remember: bool,
+) -> Result<ConnectionStatus, String> {
+    let mode = ConnectionMode::parse(&mode)?;
+    let remote_url = match remote_url.as_deref().map(str::trim) {
+        Some(raw) if !raw.is_empty() => Some(normalize_remote_url(raw)?),
+        _ => None,
+    };
+    if mode == ConnectionMode::Remote && remote_url.is_none() {
+        return Err("Remote connection requires a URL".to_string());
+    }
+
+    let persistence = match (&api_key, remember) {
+        (_, true) => ApiKeyPersistence::Remember,
+        (Some(_), false) => ApiKeyPersistence::Forget,
+        (None, false) => ApiKeyPersistence::Keep,
+    };
+    let api_key = match api_key {
+        Some(api_key) => Some(api_key.trim().to_string()).filter(|key| !key.is_empty()),
+        None => state.0.settings().await.api_key,
+    };
+
+    let settings = ConnectionSettings {
+        mode,
+        remote_url,
+        api_key,
+    };
+    state.0.prepare_switch(&settings).await?;
+
+    // Persist before switching so a failed write leaves the active connection unchanged.
+    let config_path = app_settings::config_json_path();
+    let mut config = app_settings::load_config_json(&config_path)?;
+    settings.write_to_config(&mut config, persistence)?;
+    app_settings::write_config_json(&config_path, &config)?;
+
+    Ok(state.0.switch(&app, settings).await)
+}
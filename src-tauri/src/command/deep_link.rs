@@ -0,0 +1,206 @@
+//! `bamboo://` deep-link handling
+//!
+//! Supported links:
+//! - `bamboo://session/<id>` opens a session
+//! - `bamboo://prompt?text=<prompt>[&session=<id>]` prefills the input box
+//! - `bamboo://run-workflow/<name>` runs a workflow
+//!
+//! Parsed actions are queued and the frontend is notified with
+//! [`DEEP_LINK_EVENT`]; it drains the queue via `take_pending_deep_links`, which
+//! also covers links that arrive before the webview has mounted.
+
+use crate::DeepLinkState;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, Runtime, Url};
+
+pub const DEEP_LINK_SCHEME: &str = "bamboo";
+
+/// Tauri event emitted when new deep-link actions are queued
+pub const DEEP_LINK_EVENT: &str = "deep-link-received";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum DeepLinkAction {
+    OpenSession {
+        session_id: String,
+    },
+    PrefillPrompt {
+        prompt: String,
+        session_id: Option<String>,
+    },
+    RunWorkflow {
+        name: String,
+    },
+}
+
+impl DeepLinkAction {
+    /// Action name for logs; the payload may contain user text.
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::OpenSession { .. } => "open_session",
+            Self::PrefillPrompt { .. } => "prefill_prompt",
+            Self::RunWorkflow { .. } => "run_workflow",
+        }
+    }
+}
+
+fn is_safe_identifier(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        && value != "."
+        && value != ".."
+}
+
+fn single_path_segment(url: &Url, what: &str) -> Result<String, String> {
+    let segments: Vec<&str> = url
+        .path_segments()
+        .map(|segments| segments.filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default();
+
+    match segments.as_slice() {
+        [segment] if is_safe_identifier(segment) => Ok(segment.to_string()),
+        [segment] => Err(format!("Invalid {} '{}' in deep link", what, segment)),
+        _ => Err(format!("Deep link {} must include a {}", url, what)),
+    }
+}
+
+fn query_param(url: &Url, key: &str) -> Option<String> {
+    url.query_pairs()
+        .find(|(name, _)| name == key)
+        .map(|(_, value)| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Parse a `bamboo://` URL into an action.
+pub fn parse_deep_link(url: &Url) -> Result<DeepLinkAction, String> {
+    if url.scheme() != DEEP_LINK_SCHEME {
+        return Err(format!("Unsupported deep link scheme '{}'", url.scheme()));
+    }
+
+    match url.host_str().unwrap_or_default() {
+        "session" => Ok(DeepLinkAction::OpenSession {
+            session_id: single_path_segment(url, "session id")?,
+        }),
+        "prompt" => {
+            let prompt = query_param(url, "text")
+                .ok_or_else(|| "Prompt deep link requires a 'text' parameter".to_string())?;
+            let session_id = query_param(url, "session")
+                .map(|session_id| {
+                    if is_safe_identifier(&session_id) {
+                        Ok(session_id)
+                    } else {
+                        Err(format!("Invalid session id '{}' in deep link", session_id))
+                    }
+                })
+                .transpose()?;
+            Ok(DeepLinkAction::PrefillPrompt { prompt, session_id })
+        }
+        "run-workflow" => Ok(DeepLinkAction::RunWorkflow {
+            name: single_path_segment(url, "workflow name")?,
+        }),
+        other => Err(format!("Unsupported deep link action '{}'", other)),
+    }
+}
+
+pub(crate) fn focus_main_window<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// Queue actions for opened deep links and bring the main window forward.
+pub fn handle_deep_link_urls<R: Runtime>(app: &AppHandle<R>, urls: Vec<Url>) {
+    let actions: Vec<DeepLinkAction> = urls
+        .iter()
+        .filter_map(|url| match parse_deep_link(url) {
+            Ok(action) => {
+                log::info!("Received {} deep link", action.kind());
+                Some(action)
+            }
+            Err(error) => {
+                log::warn!("Ignoring deep link: {}", error);
+                None
+            }
+        })
+        .collect();
+
+    if actions.is_empty() {
+        return;
+    }
+
+    if let Some(state) = app.try_state::<DeepLinkState>() {
+        match state.0.lock() {
+            Ok(mut pending) => pending.extend(actions),
+            Err(error) => {
+                log::warn!("Deep link queue poisoned: {}", error);
+                return;
+            }
+        }
+    }
+
+    focus_main_window(app);
+    if let Err(error) = app.emit(DEEP_LINK_EVENT, ()) {
+        log::warn!("Failed to emit deep link event: {}", error);
+    }
+}
+
+/// Drain deep-link actions that have not been handled by the frontend yet.
+#[tauri::command]
+pub fn take_pending_deep_links(
+    state: tauri::State<'_, DeepLinkState>,
+) -> Result<Vec<DeepLinkAction>, String> {
+    let mut pending = state
+        .0
+        .lock()
+        .map_err(|e| format!("Deep link queue poisoned: {}", e))?;
+    Ok(std::mem::take(&mut *pending))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(raw: &str) -> Result<DeepLinkAction, String> {
+        parse_deep_link(&Url::parse(raw).unwrap())
+    }
+
+    #[test]
+    fn parses_supported_actions() {
+        assert_eq!(
+            parse("bamboo://session/abc-123"),
+            Ok(DeepLinkAction::OpenSession {
+                session_id: "abc-123".to_string()
+            })
+        );
+        assert_eq!(
+            parse("bamboo://run-workflow/standup/"),
+            Ok(DeepLinkAction::RunWorkflow {
+                name: "standup".to_string()
+            })
+        );
+        assert_eq!(
+            parse("bamboo://prompt?text=summarize%20this&session=s1"),
+            Ok(DeepLinkAction::PrefillPrompt {
+                prompt: "summarize this".to_string(),
+                session_id: Some("s1".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_or_malformed_links() {
+        assert!(parse("https://session/abc").is_err());
+        assert!(parse("bamboo://delete-everything/now").is_err());
+        assert!(parse("bamboo://session/").is_err());
+        assert!(parse("bamboo://session/a/b").is_err());
+        assert!(parse("bamboo://run-workflow/..").is_err());
+        assert!(parse("bamboo://run-workflow/%2E%2E%2Fetc").is_err());
+        assert!(parse("bamboo://prompt").is_err());
+        assert!(parse("bamboo://new?text=hi").is_err());
+        assert!(parse("bamboo://prompt?text=hi&session=../x").is_err());
+    }
+}
@@ -1 +1,4 @@
+pub mod code_blocks;
+pub mod connection;
 pub mod copy;
+pub mod deep_link;
@@ -0,0 +1,474 @@
+//! Backend connection manager
+//!
+//! The desktop app talks either to the embedded bamboo-agent server or to a remote
+//! Bamboo web service (URL + API key). This module persists that choice in
+//! config.json, monitors the active backend's health and switches between them.
+//!
+//! The embedded server is started in either mode, since the webview still
+//! talks to it directly until it reads the base URL from
+//! `get_connection_config`.
+
+use crate::app_settings;
+use crate::embedded::EmbeddedWebService;
+use chrono::{SecondsFormat, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Runtime};
+use tokio::sync::RwLock;
+
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Tauri event emitted whenever the active backend's health or target changes
+pub const CONNECTION_STATUS_EVENT: &str = "connection-status-changed";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionMode {
+    Embedded,
+    Remote,
+}
+
+impl ConnectionMode {
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "embedded" | "" => Ok(Self::Embedded),
+            "remote" => Ok(Self::Remote),
+            _ => Err(format!("Unsupported connection mode '{}'", raw)),
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Embedded => "embedded",
+            Self::Remote => "remote",
+        }
+    }
+}
+
+/// Persisted connection choice (`connection` section of config.json)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionSettings {
+    pub mode: ConnectionMode,
+    pub remote_url: Option<String>,
+    pub api_key: Option<String>,
+}
+
+impl Default for ConnectionSettings {
+    fn default() -> Self {
+        Self {
+            mode: ConnectionMode::Embedded,
+            remote_url: None,
+            api_key: None,
+        }
+    }
+}
+
+/// How [`ConnectionSettings::write_to_config`] treats the API key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiKeyPersistence {
+    /// Store the current key, encrypted
+    Remember,
+    /// Do not store a key
+    Forget,
+    /// Leave the stored key as it is
+    Keep,
+}
+
+fn is_loopback_host(host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Normalize a remote service URL, accepting only http(s) URLs with a host.
+///
+/// Plain http is only allowed for loopback hosts since the API key is sent
+/// as a bearer token.
+pub fn normalize_remote_url(raw: &str) -> Result<String, String> {
+    let trimmed = raw.trim().trim_end_matches('/');
+    if trimmed.is_empty() {
+        return Err("Remote URL must not be empty".to_string());
+    }
+
+    let url = reqwest::Url::parse(trimmed).map_err(|e| format!("Invalid remote URL: {}", e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!(
+            "Remote URL must use http or https, got '{}'",
+            url.scheme()
+        ));
+    }
+    let host = url.host_str().unwrap_or_default();
+    if host.is_empty() {
+        return Err("Remote URL must include a host".to_string());
+    }
+    if url.scheme() == "http" && !is_loopback_host(host) {
+        return Err("Remote URL must use https unless it points to localhost".to_string());
+    }
+
+    Ok(trimmed.to_string())
+}
+
+fn decrypt_api_key(encrypted: &str) -> Option<String> {
+    match bamboo_agent::core::encryption::decrypt(encrypted) {
+        Ok(api_key) => Some(api_key),
+        Err(error) => {
+            log::warn!("Failed to decrypt remote connection API key: {}", error);
+            None
+        }
+    }
+}
+
+impl ConnectionSettings {
+    /// Load the `connection` section of config.json, defaulting to the embedded service.
+    pub fn load() -> Self {
+        let config_path = app_settings::config_json_path();
+        match app_settings::load_config_json(&config_path) {
+            Ok(config) => Self::from_config(&config),
+            Err(error) => {
+                log::warn!(
+                    "Failed to read config.json for connection settings ({}); using embedded service",
+                    error
+                );
+                Self::default()
+            }
+        }
+    }
+
+    pub fn from_config(config: &Value) -> Self {
+        let Some(section) = config.get("connection") else {
+            return Self::default();
+        };
+
+        let mode = section
+            .get("mode")
+            .and_then(Value::as_str)
+            .map(ConnectionMode::parse)
+            .transpose()
+            .unwrap_or_else(|error| {
+                log::warn!("{}; falling back to embedded connection", error);
+                None
+            })
+            .unwrap_or(ConnectionMode::Embedded);
+
+        let remote_url = section
+            .get("remote_url")
+            .and_then(Value::as_str)
+            .and_then(|raw| match normalize_remote_url(raw) {
+                Ok(url) => Some(url),
+                Err(error) => {
+                    log::warn!("Ignoring configured remote URL: {}", error);
+                    None
+                }
+            });
+
+        let api_key = section
+            .get("api_key_encrypted")
+            .and_then(Value::as_str)
+            .and_then(decrypt_api_key);
+
+        if mode == ConnectionMode::Remote && remote_url.is_none() {
+            log::warn!("Remote connection configured without a valid URL; using embedded service");
+            return Self {
+                mode: ConnectionMode::Embedded,
+                remote_url,
+                api_key,
+            };
+        }
+
+        Self {
+            mode,
+            remote_url,
+            api_key,
+        }
+    }
+
+    /// Write these settings into `config`. The API key is only ever persisted
+    /// encrypted, as selected by `api_key`.
+    pub fn write_to_config(
+        &self,
+        config: &mut Value,
+        api_key: ApiKeyPersistence,
+    ) -> Result<(), String> {
+        let config_obj = config
+            .as_object_mut()
+            .ok_or_else(|| "config.json must be a JSON object".to_string())?;
+        let stored_api_key = config_obj
+            .get("connection")
+            .and_then(|section| section.get("api_key_encrypted"))
+            .cloned();
+
+        let mut section = serde_json::Map::new();
+        section.insert(
+            "mode".to_string(),
+            Value::String(self.mode.as_str().to_string()),
+        );
+        if let Some(remote_url) = &self.remote_url {
+            section.insert("remote_url".to_string(), Value::String(remote_url.clone()));
+        }
+
+        // Never persist a plaintext API key.
+        match (api_key, self.api_key.as_deref()) {
+            (ApiKeyPersistence::Remember, Some(key)) if !key.is_empty() => {
+                let encrypted = bamboo_agent::core::encryption::encrypt(key)
+                    .map_err(|e| format!("Failed to encrypt API key: {}", e))?;
+                section.insert("api_key_encrypted".to_string(), Value::String(encrypted));
+            }
+            (ApiKeyPersistence::Keep, _) => {
+                if let Some(stored) = stored_api_key {
+                    section.insert("api_key_encrypted".to_string(), stored);
+                }
+            }
+            _ => {}
+        }
+
+        config_obj.insert("connection".to_string(), Value::Object(section));
+        Ok(())
+    }
+}
+
+/// Health snapshot of the active backend
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConnectionStatus {
+    pub mode: ConnectionMode,
+    pub base_url: String,
+    pub healthy: bool,
+    pub last_checked_at: Option<String>,
+    pub last_error: Option<String>,
+}
+
+/// Tracks which backend the desktop app is attached to
+pub struct ConnectionManager {
+    embedded: Arc<EmbeddedWebService>,
+    settings: RwLock<ConnectionSettings>,
+    status: RwLock<ConnectionStatus>,
+    client: reqwest::Client,
+}
+
+impl ConnectionManager {
+    pub fn new(embedded: Arc<EmbeddedWebService>, settings: ConnectionSettings) -> Self {
+        let base_url = Self::base_url_for(&embedded, &settings);
+        let status = ConnectionStatus {
+            mode: settings.mode,
+            base_url,
+            healthy: false,
+            last_checked_at: None,
+            last_error: None,
+        };
+
+        Self {
+            embedded,
+            settings: RwLock::new(settings),
+            status: RwLock::new(status),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn base_url_for(embedded: &EmbeddedWebService, settings: &ConnectionSettings) -> String {
+        match (settings.mode, settings.remote_url.as_ref()) {
+            (ConnectionMode::Remote, Some(remote_url)) => remote_url.clone(),
+            _ => embedded.base_url(),
+        }
+    }
+
+    pub async fn settings(&self) -> ConnectionSettings {
+        self.settings.read().await.clone()
+    }
+
+    pub async fn status(&self) -> ConnectionStatus {
+        self.status.read().await.clone()
+    }
+
+    async fn probe(&self, base_url: &str, api_key: Option<&str>) -> Result<(), String> {
+        let health_url = format!("{}/api/v1/health", base_url);
+        let mut request = self.client.get(&health_url).timeout(HEALTH_CHECK_TIMEOUT);
+        if let Some(api_key) = api_key.filter(|key| !key.is_empty()) {
+            request = request.bearer_auth(api_key);
+        }
+
+        match request.send().await {
+            Ok(response) if response.status().is_success() => Ok(()),
+            Ok(response) => Err(format!(
+                "Health check at {} returned status {}",
+                health_url,
+                response.status()
+            )),
+            Err(error) => Err(format!("Health check at {} failed: {}", health_url, error)),
+        }
+    }
+
+    /// Probe the active backend, record the result and notify the frontend when it changed.
+    ///
+    /// A result for settings that were switched away from during the probe is dropped.
+    pub async fn check_health<R: Runtime>(&self, app: &AppHandle<R>) -> ConnectionStatus {
+        let settings = self.settings().await;
+        let base_url = Self::base_url_for(&self.embedded, &settings);
+        let api_key = match settings.mode {
+            ConnectionMode::Remote => settings.api_key.as_deref(),
+            ConnectionMode::Embedded => None,
+        };
+        let result = self.probe(&base_url, api_key).await;
+
+        let next = ConnectionStatus {
+            mode: settings.mode,
+            base_url,
+            healthy: result.is_ok(),
+            last_checked_at: Some(Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)),
+            last_error: result.err(),
+        };
+
+        let changed = {
+            let mut status = self.status.write().await;
+            if *self.settings.read().await != settings {
+                return status.clone();
+            }
+            let changed = status.healthy != next.healthy
+                || status.mode != next.mode
+                || status.base_url != next.base_url;
+            *status = next.clone();
+            changed
+        };
+
+        if changed {
+            if let Some(error) = &next.last_error {
+                log::warn!("Backend connection unhealthy: {}", error);
+            } else {
+                log::info!("Backend connection healthy at {}", next.base_url);
+            }
+            if let Err(error) = app.emit(CONNECTION_STATUS_EVENT, &next) {
+                log::warn!("Failed to emit connection status event: {}", error);
+            }
+        }
+
+        next
+    }
+
+    /// Make sure the backend selected by `settings` is reachable, starting the
+    /// embedded service if needed. Does not change the active connection.
+    pub async fn prepare_switch(&self, settings: &ConnectionSettings) -> Result<(), String> {
+        match settings.mode {
+            ConnectionMode::Remote => {
+                let remote_url = settings
+                    .remote_url
+                    .as_deref()
+                    .ok_or_else(|| "Remote connection requires a URL".to_string())?;
+                self.probe(remote_url, settings.api_key.as_deref()).await?;
+            }
+            ConnectionMode::Embedded => {
+                if !self.embedded.is_running().await {
+                    self.embedded.start().await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Switch to `settings`; call [`Self::prepare_switch`] first.
+    pub async fn switch<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        settings: ConnectionSettings,
+    ) -> ConnectionStatus {
+        log::info!(
+            "Switching backend connection to {} ({})",
+            settings.mode.as_str(),
+            Self::base_url_for(&self.embedded, &settings)
+        );
+        *self.settings.write().await = settings;
+
+        self.check_health(app).await
+    }
+
+    /// Periodically re-check the active backend's health.
+    pub fn spawn_health_monitor<R: Runtime>(self: &Arc<Self>, app: AppHandle<R>) {
+        let manager = Arc::clone(self);
+        tauri::async_runtime::spawn(async move {
+            let start = tokio::time::Instant::now() + HEALTH_CHECK_INTERVAL;
+            let mut interval = tokio::time::interval_at(start, HEALTH_CHECK_INTERVAL);
+            loop {
+                interval.tick().await;
+                manager.check_health(&app).await;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_remote_urls() {
+        assert_eq!(
+            normalize_remote_url(" https://bamboo.example.com:9562/ ").unwrap(),
+            "https://bamboo.example.com:9562"
+        );
+        assert!(normalize_remote_url("").is_err());
+        assert!(normalize_remote_url("ftp://bamboo.example.com").is_err());
+        assert!(normalize_remote_url("not a url").is_err());
+    }
+
+    #[test]
+    fn requires_https_for_non_loopback_hosts() {
+        assert!(normalize_remote_url("http://10.0.0.5:9562").is_err());
+        assert!(normalize_remote_url("http://bamboo.example.com").is_err());
+        assert!(normalize_remote_url("http://localhost:9562").is_ok());
+        assert!(normalize_remote_url("http://127.0.0.1:9562").is_ok());
+        assert!(normalize_remote_url("http://[::1]:9562").is_ok());
+    }
+
+    #[test]
+    fn remote_mode_without_url_falls_back_to_embedded() {
+        let config = serde_json::json!({ "connection": { "mode": "remote" } });
+        let settings = ConnectionSettings::from_config(&config);
+        assert_eq!(settings.mode, ConnectionMode::Embedded);
+
+        let config = serde_json::json!({
+            "connection": { "mode": "remote", "remote_url": "https://10.0.0.5:9562/" }
+        });
+        let settings = ConnectionSettings::from_config(&config);
+        assert_eq!(settings.mode, ConnectionMode::Remote);
+        assert_eq!(
+            settings.remote_url.as_deref(),
+            Some("https://10.0.0.5:9562")
+        );
+    }
+
+    #[test]
+    fn api_key_is_not_persisted_without_remember() {
+        let settings = ConnectionSettings {
+            mode: ConnectionMode::Remote,
+            remote_url: Some("https://10.0.0.5:9562".to_string()),
+            api_key: Some("secret".to_string()),
+        };
+        let mut config = serde_json::json!({
+            "http_proxy": "",
+            "connection": { "mode": "embedded", "api_key_encrypted": "stored" }
+        });
+
+        settings
+            .write_to_config(&mut config, ApiKeyPersistence::Keep)
+            .unwrap();
+        assert_eq!(
+            config["connection"],
+            serde_json::json!({
+                "mode": "remote",
+                "remote_url": "https://10.0.0.5:9562",
+                "api_key_encrypted": "stored"
+            })
+        );
+
+        settings
+            .write_to_config(&mut config, ApiKeyPersistence::Forget)
+            .unwrap();
+        assert_eq!(
+            config["connection"],
+            serde_json::json!({ "mode": "remote", "remote_url": "https://10.0.0.5:9562" })
+        );
+        assert_eq!(config["http_proxy"], "");
+    }
+}
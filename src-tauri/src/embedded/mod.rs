@@ -166,6 +166,15 @@ impl EmbeddedWebService {
         }
     }
 
+    /// Base URL the desktop app uses to reach the embedded server
+    pub fn base_url(&self) -> String {
+        format!(
+            "http://{}:{}",
+            loopback_probe_host(&self.bind_addr),
+            self.port
+        )
+    }
+
     /// Start the embedded HTTP server
     ///
     /// This uses bamboo-agent's managed WebService lifecycle to avoid Send constraints.
@@ -255,8 +264,7 @@ impl EmbeddedWebService {
 
     /// Wait for the web service to become healthy
     async fn wait_for_health(&self) -> Result<(), String> {
-        let probe_host = loopback_probe_host(&self.bind_addr);
-        let health_url = format!("http://{}:{}/api/v1/health", probe_host, self.port);
+        let health_url = format!("{}/api/v1/health", self.base_url());
         let client = reqwest::Client::new();
 
         info!(
@@ -301,8 +309,7 @@ impl EmbeddedWebService {
 
     /// Check if service is running by testing health endpoint
     pub async fn is_running(&self) -> bool {
-        let probe_host = loopback_probe_host(&self.bind_addr);
-        let health_url = format!("http://{}:{}/api/v1/health", probe_host, self.port);
+        let health_url = format!("{}/api/v1/health", self.base_url());
         let client = reqwest::Client::new();
 
         match client
@@ -1,4 +1,12 @@
+use crate::command::code_blocks::{list_code_blocks, save_code_blocks};
+use crate::command::connection::{
+    get_connection_config, get_connection_status, set_connection_config,
+};
 use crate::command::copy::copy_to_clipboard;
+use crate::command::deep_link::{
+    focus_main_window, handle_deep_link_urls, take_pending_deep_links, DeepLinkAction,
+};
+use crate::connection::{ConnectionManager, ConnectionSettings};
 use crate::embedded::EmbeddedWebService;
 use chrono::{SecondsFormat, Utc};
 use log::{info, LevelFilter};
@@ -7,18 +15,27 @@ use std::sync::Arc;
 use std::time::Duration;
 use tauri::Manager;
 use tauri::{App, Runtime};
+use tauri_plugin_deep_link::DeepLinkExt;
 use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
 use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut};
-use tauri_plugin_log::{Target, TargetKind};
+use tauri_plugin_log::{RotationStrategy, Target, TargetKind};
 use tokio::time::sleep;
 
 pub mod app_settings;
 pub mod command;
+pub mod connection;
 pub mod embedded;
+pub mod logging;
 
 // Embedded web service state wrapper for Tauri state management
 pub struct WebServiceState(pub Arc<EmbeddedWebService>);
 
+// Backend connection manager (embedded or remote) for Tauri state management
+pub struct ConnectionState(pub Arc<ConnectionManager>);
+
+// Deep-link actions waiting for the frontend to pick them up
+pub struct DeepLinkState(pub std::sync::Mutex<Vec<DeepLinkAction>>);
+
 fn read_config_json() -> Result<Value, String> {
     let config_path = app_settings::config_json_path();
     app_settings::load_config_json(&config_path)
@@ -229,15 +246,47 @@ fn show_internal_startup_confirmation<R: Runtime>(app: &App<R>) {
         });
 }
 
-fn setup<R: Runtime>(app: &mut App<R>) -> std::result::Result<(), Box<dyn std::error::Error>> {
+fn setup_deep_links<R: Runtime>(app: &App<R>) {
+    app.manage(DeepLinkState(Default::default()));
+
+    // macOS registers the scheme from the bundle; Windows/Linux need runtime registration.
+    #[cfg(any(windows, target_os = "linux"))]
+    if let Err(error) = app.deep_link().register_all() {
+        log::warn!("Failed to register deep link schemes: {}", error);
+    }
+
+    let app_handle = app.handle().clone();
+    app.deep_link().on_open_url(move |event| {
+        handle_deep_link_urls(&app_handle, event.urls());
+    });
+
+    match app.deep_link().get_current() {
+        Ok(Some(urls)) => handle_deep_link_urls(app.handle(), urls),
+        Ok(None) => {}
+        Err(error) => log::warn!("Failed to read startup deep link: {}", error),
+    }
+}
+
+fn setup<R: Runtime>(
+    app: &mut App<R>,
+    log_rotation: logging::LogRotationConfig,
+) -> std::result::Result<(), Box<dyn std::error::Error>> {
     let app_data_dir = app_settings::bamboo_dir();
     std::fs::create_dir_all(&app_data_dir)?;
     info!("App data dir: {:?}", app_data_dir);
 
+    logging::schedule_log_maintenance(log_rotation);
+
     // Start embedded web service
     let web_service = Arc::new(EmbeddedWebService::new(9562, app_data_dir.clone()));
+    let connection = Arc::new(ConnectionManager::new(
+        Arc::clone(&web_service),
+        ConnectionSettings::load(),
+    ));
 
     let web_service_clone = Arc::clone(&web_service);
+    // Started even in remote mode: the webview still calls the embedded port
+    // until the frontend routes its API traffic through `get_connection_config`.
     tauri::async_runtime::spawn(async move {
         // If an external backend is already running on the port,
         // don't try to start the embedded server (avoids noisy bind/health failures).
@@ -257,6 +306,11 @@ fn setup<R: Runtime>(app: &mut App<R>) -> std::result::Result<(), Box<dyn std::e
     // Manage web service state for later access
     app.manage(WebServiceState(web_service));
 
+    connection.spawn_health_monitor(app.handle().clone());
+    app.manage(ConnectionState(connection));
+
+    setup_deep_links(app);
+
     show_internal_startup_confirmation(app);
     maybe_open_devtools(app);
     schedule_webview_diag(app);
@@ -427,13 +481,18 @@ fn toggle_main_window<R: Runtime>(app: &tauri::AppHandle<R>) {
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // Rotated files are kept by the plugin and pruned by `logging::enforce_retention`.
+    // The logger does not exist yet, so a config warning is logged in `setup`.
+    let (log_rotation, log_rotation_warning) = logging::LogRotationConfig::load();
     let log_plugin = tauri_plugin_log::Builder::new()
         .level(LevelFilter::Debug)
+        .max_file_size(log_rotation.max_file_size_bytes())
+        .rotation_strategy(RotationStrategy::KeepAll)
         .clear_targets()
         .targets([
             Target::new(TargetKind::Stdout),
             Target::new(TargetKind::Folder {
-                path: app_settings::bamboo_dir().join("logs"),
+                path: logging::logs_dir(),
                 file_name: None,
             }),
         ])
@@ -442,13 +501,23 @@ pub fn run() {
     let fs_plugin = tauri_plugin_fs::init();
 
     tauri::Builder::default()
+        // Must be registered first so a second launch (e.g. from a deep link)
+        // is forwarded to the running instance.
+        .plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
+            focus_main_window(app);
+        }))
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(fs_plugin)
         .plugin(log_plugin)
         .plugin(dialog_plugin)
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_process::init())
-        .setup(|app| {
+        .setup(move |app| {
+            if let Some(warning) = log_rotation_warning {
+                log::warn!("{}", warning);
+            }
+
             // Register global shortcut: Cmd+Shift+Space (or Ctrl+Shift+Space on Windows/Linux)
             #[cfg(target_os = "macos")]
             let shortcut = Shortcut::new(Some(Modifiers::SUPER | Modifiers::SHIFT), Code::Space);
@@ -466,14 +535,20 @@ pub fn run() {
                 log::info!("Global shortcut registered: Cmd/Ctrl+Shift+Space");
             }
 
-            setup(app)
+            setup(app, log_rotation)
         })
         .invoke_handler(tauri::generate_handler![
             copy_to_clipboard,
+            get_connection_config,
+            get_connection_status,
             get_proxy_config,
+            list_code_blocks,
             mark_setup_incomplete,
+            save_code_blocks,
+            set_connection_config,
             set_proxy_config,
             set_window_theme,
+            take_pending_deep_links,
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
@@ -0,0 +1,334 @@
+//! File log rotation and retention
+//!
+//! The Tauri log plugin rotates `~/.bamboo/logs/<app>.log` by size into
+//! `<app>_<timestamp>.log` files. This module reads the `logging` section of
+//! config.json and runs a background maintenance pass that compresses rotated
+//! files and prunes them by count and age.
+
+use crate::app_settings;
+use chrono::{NaiveDateTime, Utc};
+use regex::Regex;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const ROTATED_TIMESTAMP_FORMAT: &str = "%Y-%m-%d_%H-%M-%S";
+const PARTIAL_SUFFIX: &str = ".partial";
+
+/// `logging` section of config.json
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct LogRotationConfig {
+    /// Size at which the active log file is rotated
+    pub max_file_size_mb: u64,
+    /// Number of rotated files to keep (0 keeps all)
+    pub max_files: usize,
+    /// Delete rotated files older than this many days (0 disables)
+    pub max_age_days: u64,
+    /// Compress rotated files with zstd
+    pub compress: bool,
+}
+
+impl Default for LogRotationConfig {
+    fn default() -> Self {
+        Self {
+            max_file_size_mb: 10,
+            max_files: 10,
+            max_age_days: 14,
+            compress: true,
+        }
+    }
+}
+
+impl LogRotationConfig {
+    /// Load the rotation settings from config.json, falling back to defaults.
+    ///
+    /// This runs before the logger is set up, so the fallback warning is
+    /// returned for the caller to log once logging is available.
+    pub fn load() -> (Self, Option<String>) {
+        let config_path = app_settings::config_json_path();
+        app_settings::load_config_json(&config_path)
+            .map_err(|error| {
+                format!(
+                    "Failed to read config.json for log rotation ({}); using defaults",
+                    error
+                )
+            })
+            .and_then(|config| Self::from_config(&config))
+            .map_or_else(
+                |warning| (Self::default(), Some(warning)),
+                |config| (config, None),
+            )
+    }
+
+    fn from_config(config: &serde_json::Value) -> Result<Self, String> {
+        let Some(section) = config.get("logging") else {
+            return Ok(Self::default());
+        };
+
+        serde_json::from_value(section.clone())
+            .map_err(|error| format!("Invalid logging config ({}); using defaults", error))
+    }
+
+    pub fn max_file_size_bytes(&self) -> u128 {
+        u128::from(self.max_file_size_mb.max(1)) * 1024 * 1024
+    }
+}
+
+pub fn logs_dir() -> PathBuf {
+    app_settings::bamboo_dir().join("logs")
+}
+
+struct RotatedLog {
+    path: PathBuf,
+    rotated_at: NaiveDateTime,
+    compressed: bool,
+}
+
+fn rotated_log_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"^.+_(\d{4}-\d{2}-\d{2}_\d{2}-\d{2}-\d{2})\.log(\.zst)?$")
+            .expect("rotated log pattern is valid")
+    })
+}
+
+fn list_rotated_logs(dir: &Path) -> std::io::Result<Vec<RotatedLog>> {
+    let mut logs = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if !path.is_file() {
+            continue;
+        }
+        let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let Some(captures) = rotated_log_pattern().captures(file_name) else {
+            continue;
+        };
+        let Ok(rotated_at) = NaiveDateTime::parse_from_str(&captures[1], ROTATED_TIMESTAMP_FORMAT)
+        else {
+            continue;
+        };
+        let compressed = captures.get(2).is_some();
+        logs.push(RotatedLog {
+            path,
+            rotated_at,
+            compressed,
+        });
+    }
+    Ok(logs)
+}
+
+fn write_compressed(source: &Path, partial: &Path, target: &Path) -> std::io::Result<()> {
+    let mut input = std::fs::File::open(source)?;
+    let output = std::fs::File::create(partial)?;
+    let mut encoder = zstd::Encoder::new(output, 3)?;
+    std::io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?;
+    std::fs::rename(partial, target)
+}
+
+fn compress_log(path: &Path) -> std::io::Result<()> {
+    let mut target = path.as_os_str().to_owned();
+    target.push(".zst");
+    let target = PathBuf::from(target);
+    let mut partial = target.as_os_str().to_owned();
+    partial.push(PARTIAL_SUFFIX);
+    let partial = PathBuf::from(partial);
+
+    if let Err(error) = write_compressed(path, &partial, &target) {
+        let _ = std::fs::remove_file(&partial);
+        return Err(error);
+    }
+
+    std::fs::remove_file(path)
+}
+
+/// Remove `*.log.zst.partial` files left behind by an interrupted compression.
+fn remove_stale_partials(dir: &Path) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_partial = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with(&format!(".log.zst{}", PARTIAL_SUFFIX)));
+        if is_partial && path.is_file() {
+            if let Err(error) = std::fs::remove_file(&path) {
+                log::warn!("Failed to remove partial log {:?}: {}", path, error);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Compress rotated logs and prune them according to `config`.
+///
+/// The active log file is never touched. A file that cannot be deleted or
+/// compressed is logged and skipped so the rest of the pass still runs.
+pub fn enforce_retention(
+    dir: &Path,
+    config: &LogRotationConfig,
+    now: NaiveDateTime,
+) -> std::io::Result<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+
+    remove_stale_partials(dir)?;
+
+    let mut logs = list_rotated_logs(dir)?;
+    // Newest first so retention keeps the most recent files.
+    logs.sort_by_key(|rotated| std::cmp::Reverse(rotated.rotated_at));
+
+    // Ages too large for a chrono duration are treated as no limit.
+    let max_age = Some(config.max_age_days)
+        .filter(|days| *days > 0)
+        .and_then(|days| i64::try_from(days).ok())
+        .and_then(chrono::Duration::try_days);
+
+    for (position, rotated) in logs.iter().enumerate() {
+        let over_count = config.max_files > 0 && position >= config.max_files;
+        let too_old = max_age.is_some_and(|max_age| now - rotated.rotated_at > max_age);
+        if over_count || too_old {
+            if let Err(error) = std::fs::remove_file(&rotated.path) {
+                log::warn!("Failed to delete rotated log {:?}: {}", rotated.path, error);
+            }
+            continue;
+        }
+
+        if config.compress && !rotated.compressed {
+            if let Err(error) = compress_log(&rotated.path) {
+                log::warn!(
+                    "Failed to compress rotated log {:?}: {}",
+                    rotated.path,
+                    error
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Run log maintenance now and then hourly in the background.
+pub fn schedule_log_maintenance(config: LogRotationConfig) {
+    let dir = logs_dir();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(MAINTENANCE_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let dir = dir.clone();
+            let config = config.clone();
+            let result = tokio::task::spawn_blocking(move || {
+                enforce_retention(&dir, &config, Utc::now().naive_utc())
+            })
+            .await;
+
+            match result {
+                Ok(Ok(())) => {}
+                Ok(Err(error)) => log::warn!("Log retention pass failed: {}", error),
+                Err(error) => log::warn!("Log retention task panicked: {}", error),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(raw: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(raw, ROTATED_TIMESTAMP_FORMAT).unwrap()
+    }
+
+    #[test]
+    fn config_defaults_when_section_missing_or_invalid() {
+        let missing = serde_json::json!({});
+        assert_eq!(
+            LogRotationConfig::from_config(&missing),
+            Ok(LogRotationConfig::default())
+        );
+
+        let invalid = serde_json::json!({ "logging": { "max_files": "many" } });
+        assert!(LogRotationConfig::from_config(&invalid).is_err());
+
+        let partial = serde_json::json!({ "logging": { "max_files": 3, "compress": false } });
+        let config = LogRotationConfig::from_config(&partial).unwrap();
+        assert_eq!(config.max_files, 3);
+        assert!(!config.compress);
+        assert_eq!(config.max_age_days, 14);
+    }
+
+    #[test]
+    fn huge_max_age_disables_age_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = dir.path().join("Bodhi_2000-01-01_00-00-00.log");
+        std::fs::write(&old, "x").unwrap();
+
+        let config = LogRotationConfig {
+            max_age_days: u64::MAX,
+            compress: false,
+            ..LogRotationConfig::default()
+        };
+        enforce_retention(dir.path(), &config, at("2026-10-16_00-00-00")).unwrap();
+
+        assert!(old.exists());
+    }
+
+    #[test]
+    fn compresses_rotated_logs_and_keeps_active_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("Bodhi.log"), "active").unwrap();
+        std::fs::write(dir.path().join("Bodhi_2026-10-15_08-00-00.log"), "old").unwrap();
+        let stale = dir.path().join("Bodhi_2026-10-14_08-00-00.log.zst.partial");
+        std::fs::write(&stale, "interrupted").unwrap();
+
+        let config = LogRotationConfig::default();
+        enforce_retention(dir.path(), &config, at("2026-10-16_08-00-00")).unwrap();
+
+        assert!(dir.path().join("Bodhi.log").is_file());
+        assert!(!stale.exists());
+        assert!(!dir.path().join("Bodhi_2026-10-15_08-00-00.log").exists());
+        let compressed = dir.path().join("Bodhi_2026-10-15_08-00-00.log.zst");
+        let decoded = zstd::decode_all(std::fs::File::open(compressed).unwrap()).unwrap();
+        assert_eq!(decoded, b"old");
+    }
+
+    #[test]
+    fn prunes_by_count_and_age() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in [
+            "Bodhi_2026-10-15_00-00-00.log",
+            "Bodhi_2026-10-14_00-00-00.log.zst",
+            "Bodhi_2026-10-13_00-00-00.log",
+            "Bodhi_2026-09-01_00-00-00.log",
+        ] {
+            std::fs::write(dir.path().join(name), "x").unwrap();
+        }
+
+        let config = LogRotationConfig {
+            max_files: 2,
+            max_age_days: 7,
+            compress: false,
+            ..LogRotationConfig::default()
+        };
+        enforce_retention(dir.path(), &config, at("2026-10-16_00-00-00")).unwrap();
+
+        let mut remaining: Vec<String> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        remaining.sort();
+        assert_eq!(
+            remaining,
+            vec![
+                "Bodhi_2026-10-14_00-00-00.log.zst".to_string(),
+                "Bodhi_2026-10-15_00-00-00.log".to_string(),
+            ]
+        );
+    }
+}